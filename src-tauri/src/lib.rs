@@ -1,6 +1,12 @@
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::Manager;
@@ -19,27 +25,565 @@ pub struct Note {
     #[serde(default)]
     pub deleted: bool,
     pub deleted_at: Option<u64>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 fn default_list() -> String {
     "My Notes".to_string()
 }
 
+/// token -> (note id -> occurrence count)
+type InvertedIndex = HashMap<String, HashMap<String, usize>>;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const VAULT_VERSION: u8 = 1;
+
+/// The Argon2id-derived key for a vault's encrypted store, kept only in
+/// memory for the duration of the session.
+pub struct VaultKey {
+    key: [u8; 32],
+    salt: [u8; SALT_LEN],
+}
+
+#[derive(Serialize, Clone)]
+pub struct SearchHit {
+    pub note: Note,
+    pub score: f64,
+    pub snippet: String,
+}
+
+pub type VaultId = String;
+
+const DEFAULT_VAULT_ID: &str = "default";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct VaultInfo {
+    pub id: VaultId,
+    pub name: String,
+}
+
+/// Everything one note collection needs: its own connection, search
+/// index, and (optional) encryption key, all independent of every other
+/// vault's.
+pub struct VaultState {
+    pub conn: Mutex<Connection>,
+    pub db_path: PathBuf,
+    pub index: Mutex<InvertedIndex>,
+    pub vault_key: Mutex<Option<VaultKey>>,
+}
+
 pub struct AppState {
-    pub notes: Mutex<Vec<Note>>,
-    pub file_path: PathBuf,
+    pub data_dir: PathBuf,
+    pub vaults: Mutex<HashMap<VaultId, VaultState>>,
+    pub active: Mutex<VaultId>,
+}
+
+/// Runs `f` against the currently active vault's state.
+fn with_active<T>(
+    state: &AppState,
+    f: impl FnOnce(&VaultState) -> Result<T, String>,
+) -> Result<T, String> {
+    let active = state.active.lock().map_err(|e| e.to_string())?.clone();
+    let vaults = state.vaults.lock().map_err(|e| e.to_string())?;
+    let vault = vaults
+        .get(&active)
+        .ok_or_else(|| format!("active vault {} not found", active))?;
+    f(vault)
+}
+
+/// Like `with_active`, but for commands that mutate vault data: once a
+/// passphrase is set, re-encrypts the on-disk snapshot afterward so it
+/// never drifts from what's sitting in the (in-memory, while unlocked)
+/// connection.
+fn with_active_mut<T>(
+    state: &AppState,
+    f: impl FnOnce(&VaultState) -> Result<T, String>,
+) -> Result<T, String> {
+    with_active(state, |vault| {
+        let result = f(vault)?;
+        persist_if_encrypted(vault)?;
+        Ok(result)
+    })
+}
+
+/// Re-encrypts the active vault's export onto `db_path` if a passphrase is
+/// set; a no-op for a vault that has never called `set_passphrase`.
+fn persist_if_encrypted(vault: &VaultState) -> Result<(), String> {
+    let vault_key = vault.vault_key.lock().map_err(|e| e.to_string())?;
+    if let Some(vault_key) = vault_key.as_ref() {
+        let conn = vault.conn.lock().map_err(|e| e.to_string())?;
+        let export = export_vault(&conn)?;
+        drop(conn);
+        fs::write(&vault.db_path, encrypt_vault(&export, vault_key)?).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize)]
+struct Settings {
+    #[serde(default = "default_retention_days")]
+    trash_retention_days: u32,
+}
+
+fn default_retention_days() -> u32 {
+    30
+}
+
+fn settings_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("settings.json")
+}
+
+fn load_settings(data_dir: &Path) -> Settings {
+    fs::read_to_string(settings_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or(Settings {
+            trash_retention_days: default_retention_days(),
+        })
 }
 
-fn load_notes(path: &PathBuf) -> Vec<Note> {
-    fs::read_to_string(path)
+fn save_settings(data_dir: &Path, settings: &Settings) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(settings_path(data_dir), json).map_err(|e| e.to_string())
+}
+
+/// Hard-deletes trashed notes past the retention window, along with their
+/// tags and revision history. Returns how many notes were removed.
+fn sweep_trash(conn: &Connection, retention_days: u32) -> Result<usize, String> {
+    let cutoff = now_unix().saturating_sub(retention_days as u64 * 86_400);
+    let ids: Vec<String> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id FROM notes WHERE deleted = 1 AND deleted_at IS NOT NULL AND deleted_at <= ?1",
+            )
+            .map_err(|e| e.to_string())?;
+        stmt.query_map(params![cutoff], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?
+    };
+    purge_notes(conn, &ids)?;
+    Ok(ids.len())
+}
+
+/// Hard-deletes `ids` from `notes`, `note_tags`, and `revisions` in one go.
+fn purge_notes(conn: &Connection, ids: &[String]) -> Result<(), String> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+    let placeholders = (1..=ids.len())
+        .map(|i| format!("?{}", i))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let params_vec: Vec<&dyn rusqlite::ToSql> = ids.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
+    for (table, column) in [("note_tags", "note_id"), ("revisions", "note_id"), ("notes", "id")] {
+        conn.execute(
+            &format!("DELETE FROM {} WHERE {} IN ({})", table, column, placeholders),
+            params_vec.as_slice(),
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn vaults_manifest_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("vaults.json")
+}
+
+fn active_vault_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("active_vault")
+}
+
+fn vault_db_path(data_dir: &Path, id: &str) -> PathBuf {
+    if id == DEFAULT_VAULT_ID {
+        // Keeps working on the single-vault `notes.db` that predates this
+        // feature instead of forcing a file move on upgrade.
+        data_dir.join("notes.db")
+    } else {
+        data_dir.join("vaults").join(format!("{}.db", id))
+    }
+}
+
+fn load_vaults_manifest(data_dir: &Path) -> Vec<VaultInfo> {
+    fs::read_to_string(vaults_manifest_path(data_dir))
         .ok()
         .and_then(|s| serde_json::from_str(&s).ok())
         .unwrap_or_default()
 }
 
-fn save_notes(path: &PathBuf, notes: &[Note]) -> Result<(), String> {
-    let json = serde_json::to_string_pretty(notes).map_err(|e| e.to_string())?;
-    fs::write(path, json).map_err(|e| e.to_string())
+fn save_vaults_manifest(data_dir: &Path, vaults: &[VaultInfo]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(vaults).map_err(|e| e.to_string())?;
+    fs::write(vaults_manifest_path(data_dir), json).map_err(|e| e.to_string())
+}
+
+fn load_active_vault_id(data_dir: &Path) -> Option<VaultId> {
+    fs::read_to_string(active_vault_path(data_dir))
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+fn save_active_vault_id(data_dir: &Path, id: &str) -> Result<(), String> {
+    fs::write(active_vault_path(data_dir), id).map_err(|e| e.to_string())
+}
+
+/// Opens a single vault's database (creating it and importing the legacy
+/// `notes.json` for the default vault on first run), ready to be dropped
+/// into the `vaults` map.
+fn open_vault_state(data_dir: &Path, id: &str, retention_days: u32) -> Result<VaultState, String> {
+    let db_path = vault_db_path(data_dir, id);
+    if let Some(parent) = db_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let db_is_new = !db_path.exists();
+
+    let (conn, locked) = if db_is_new {
+        let mut conn = open_connection(&db_path)?;
+        let mut locked = false;
+        if id == DEFAULT_VAULT_ID {
+            let legacy_json_path = data_dir.join("notes.json");
+            if let Some(legacy_notes) = load_plaintext_notes(&legacy_json_path) {
+                import_notes(&conn, &legacy_notes)?;
+                fs::remove_file(&legacy_json_path).map_err(|e| e.to_string())?;
+            } else if legacy_json_path.exists() {
+                // Not valid JSON: a vault encrypted under the old
+                // single-vault scheme. Carry the ciphertext over as this
+                // vault's at-rest snapshot instead of silently discarding
+                // it, so `unlock` can still recover it.
+                conn = Connection::open_in_memory().map_err(|e| e.to_string())?;
+                fs::remove_file(&db_path).map_err(|e| e.to_string())?;
+                fs::rename(&legacy_json_path, &db_path).map_err(|e| e.to_string())?;
+                locked = true;
+            }
+        }
+        (conn, locked)
+    } else if probe_unlocked(&db_path) {
+        (open_connection(&db_path)?, false)
+    } else {
+        // Encrypted vault from a previous session; stays locked until the
+        // frontend calls `unlock`.
+        (Connection::open_in_memory().map_err(|e| e.to_string())?, true)
+    };
+
+    if !locked {
+        sweep_trash(&conn, retention_days)?;
+    }
+
+    let notes = fetch_all_notes(&conn).unwrap_or_default();
+    let index = build_index(&notes);
+    Ok(VaultState {
+        conn: Mutex::new(conn),
+        db_path,
+        index: Mutex::new(index),
+        vault_key: Mutex::new(None),
+    })
+}
+
+/// Opens (or creates) a vault's database and ensures the schema exists.
+fn open_connection(path: &PathBuf) -> Result<Connection, String> {
+    let conn = Connection::open(path).map_err(|e| e.to_string())?;
+    init_schema(&conn)?;
+    Ok(conn)
+}
+
+/// Creates the `notes`/`note_tags`/`revisions` schema on `conn` if it
+/// isn't already there. Shared by `open_connection` and by the in-memory
+/// connections an encrypted vault runs on while unlocked.
+fn init_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS notes (
+            id TEXT PRIMARY KEY,
+            content TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            pinned INTEGER NOT NULL DEFAULT 0,
+            list TEXT NOT NULL,
+            deleted INTEGER NOT NULL DEFAULT 0,
+            deleted_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_notes_list ON notes(list);
+        CREATE INDEX IF NOT EXISTS idx_notes_deleted ON notes(deleted);
+        CREATE TABLE IF NOT EXISTS note_tags (
+            note_id TEXT NOT NULL,
+            tag TEXT NOT NULL,
+            PRIMARY KEY (note_id, tag)
+        );
+        CREATE INDEX IF NOT EXISTS idx_note_tags_tag ON note_tags(tag);
+        CREATE TABLE IF NOT EXISTS revisions (
+            id TEXT PRIMARY KEY,
+            note_id TEXT NOT NULL,
+            content TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_revisions_note_id ON revisions(note_id);",
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// A database file that opens but rejects a trivial read is an encrypted
+/// vault from a previous session; everything else counts as readable
+/// without a passphrase.
+fn probe_unlocked(path: &PathBuf) -> bool {
+    Connection::open(path)
+        .and_then(|conn| conn.query_row("SELECT count(*) FROM sqlite_master", [], |_| Ok(())))
+        .is_ok()
+}
+
+/// Loads the legacy plain-JSON store, if `path` holds one, for the
+/// one-time `notes.json` -> SQLite import.
+fn load_plaintext_notes(path: &PathBuf) -> Option<Vec<Note>> {
+    let s = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&s).ok()
+}
+
+fn import_notes(conn: &Connection, notes: &[Note]) -> Result<(), String> {
+    for note in notes {
+        conn.execute(
+            "INSERT OR IGNORE INTO notes
+                (id, content, created_at, updated_at, pinned, list, deleted, deleted_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                note.id,
+                note.content,
+                note.created_at,
+                note.updated_at,
+                note.pinned as i64,
+                note.list,
+                note.deleted as i64,
+                note.deleted_at,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        for tag in &note.tags {
+            conn.execute(
+                "INSERT OR IGNORE INTO note_tags (note_id, tag) VALUES (?1, ?2)",
+                params![note.id, tag],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Leaves `tags` empty; the notes table doesn't carry them, so callers
+/// fill it in separately with `fetch_tags`.
+fn row_to_note(row: &rusqlite::Row) -> rusqlite::Result<Note> {
+    Ok(Note {
+        id: row.get(0)?,
+        content: row.get(1)?,
+        created_at: row.get(2)?,
+        updated_at: row.get(3)?,
+        pinned: row.get::<_, i64>(4)? != 0,
+        list: row.get(5)?,
+        deleted: row.get::<_, i64>(6)? != 0,
+        deleted_at: row.get(7)?,
+        tags: Vec::new(),
+    })
+}
+
+const NOTE_COLUMNS: &str = "id, content, created_at, updated_at, pinned, list, deleted, deleted_at";
+
+fn fetch_tags(conn: &Connection, note_id: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT tag FROM note_tags WHERE note_id = ?1 ORDER BY tag")
+        .map_err(|e| e.to_string())?;
+    stmt.query_map(params![note_id], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+fn fetch_all_notes(conn: &Connection) -> Result<Vec<Note>, String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT {} FROM notes", NOTE_COLUMNS))
+        .map_err(|e| e.to_string())?;
+    let mut notes = stmt
+        .query_map([], row_to_note)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    for note in notes.iter_mut() {
+        note.tags = fetch_tags(conn, &note.id)?;
+    }
+    Ok(notes)
+}
+
+fn fetch_note(conn: &Connection, id: &str) -> Result<Note, String> {
+    let mut note = conn
+        .query_row(
+            &format!("SELECT {} FROM notes WHERE id = ?1", NOTE_COLUMNS),
+            params![id],
+            row_to_note,
+        )
+        .map_err(|_| format!("Note {} not found", id))?;
+    note.tags = fetch_tags(conn, id)?;
+    Ok(note)
+}
+
+fn normalize_tag(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+#[derive(Serialize, Clone)]
+pub struct Revision {
+    pub id: String,
+    pub content: String,
+    pub created_at: u64,
+}
+
+const MAX_REVISIONS: i64 = 50;
+const REVISION_COALESCE_WINDOW_SECS: u64 = 60;
+
+/// Drops everything but the newest `MAX_REVISIONS` revisions for a note.
+fn cap_revisions(conn: &Connection, note_id: &str) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM revisions WHERE note_id = ?1 AND id NOT IN (
+            SELECT id FROM revisions WHERE note_id = ?1 ORDER BY created_at DESC LIMIT ?2
+        )",
+        params![note_id, MAX_REVISIONS],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Snapshots `old_content` as a new revision of `note_id`, unless the most
+/// recent revision is under a minute old, in which case this edit is
+/// folded into it instead of creating a near-duplicate entry.
+fn snapshot_revision(conn: &Connection, note_id: &str, old_content: &str) -> Result<(), String> {
+    let now = now_unix();
+    let last_created_at: Option<u64> = conn
+        .query_row(
+            "SELECT created_at FROM revisions WHERE note_id = ?1 ORDER BY created_at DESC LIMIT 1",
+            params![note_id],
+            |row| row.get(0),
+        )
+        .ok();
+    if let Some(last) = last_created_at {
+        if now.saturating_sub(last) < REVISION_COALESCE_WINDOW_SECS {
+            return Ok(());
+        }
+    }
+
+    conn.execute(
+        "INSERT INTO revisions (id, note_id, content, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![Uuid::new_v4().to_string(), note_id, old_content, now],
+    )
+    .map_err(|e| e.to_string())?;
+    cap_revisions(conn, note_id)
+}
+
+fn derive_key(passphrase: &str, salt: &[u8; SALT_LEN]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+/// Everything an encrypted vault needs to round-trip through a passphrase:
+/// notes (with their tags) plus their revision history, which otherwise
+/// lives only in the live connection's `revisions` table.
+#[derive(Serialize, Deserialize)]
+struct VaultExport {
+    notes: Vec<Note>,
+    revisions: Vec<RevisionExport>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RevisionExport {
+    note_id: String,
+    id: String,
+    content: String,
+    created_at: u64,
+}
+
+fn export_vault(conn: &Connection) -> Result<VaultExport, String> {
+    let notes = fetch_all_notes(conn)?;
+    let mut stmt = conn
+        .prepare("SELECT note_id, id, content, created_at FROM revisions")
+        .map_err(|e| e.to_string())?;
+    let revisions = stmt
+        .query_map([], |row| {
+            Ok(RevisionExport {
+                note_id: row.get(0)?,
+                id: row.get(1)?,
+                content: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(VaultExport { notes, revisions })
+}
+
+fn import_vault(conn: &Connection, export: &VaultExport) -> Result<(), String> {
+    import_notes(conn, &export.notes)?;
+    for revision in &export.revisions {
+        conn.execute(
+            "INSERT OR IGNORE INTO revisions (id, note_id, content, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![revision.id, revision.note_id, revision.content, revision.created_at],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Serializes `export` and seals it with XChaCha20-Poly1305 under a fresh
+/// random nonce, prefixed with a `{ version, salt, nonce }` header. This is
+/// the entire on-disk representation of an encrypted vault.
+fn encrypt_vault(export: &VaultExport, vault_key: &VaultKey) -> Result<Vec<u8>, String> {
+    let plaintext = serde_json::to_vec(export).map_err(|e| e.to_string())?;
+    let cipher = XChaCha20Poly1305::new((&vault_key.key).into());
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+    let ciphertext = cipher
+        .encrypt(XNonce::from_slice(&nonce_bytes), plaintext.as_ref())
+        .map_err(|e| e.to_string())?;
+
+    let mut out = Vec::with_capacity(1 + SALT_LEN + NONCE_LEN + ciphertext.len());
+    out.push(VAULT_VERSION);
+    out.extend_from_slice(&vault_key.salt);
+    out.extend_from_slice(&nonce_bytes);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+fn header_salt(data: &[u8]) -> Result<[u8; SALT_LEN], String> {
+    if data.len() < 1 + SALT_LEN {
+        return Err("corrupt vault file".to_string());
+    }
+    let mut salt = [0u8; SALT_LEN];
+    salt.copy_from_slice(&data[1..1 + SALT_LEN]);
+    Ok(salt)
+}
+
+/// Decrypts a `{ version, salt, nonce }`-prefixed vault file with `key`.
+/// Fails distinctly on MAC verification failure so callers can report a
+/// wrong passphrase rather than a generic error.
+fn decrypt_vault(data: &[u8], key: &[u8; 32]) -> Result<VaultExport, String> {
+    if data.len() < 1 + SALT_LEN + NONCE_LEN {
+        return Err("corrupt vault file".to_string());
+    }
+    let nonce = XNonce::from_slice(&data[1 + SALT_LEN..1 + SALT_LEN + NONCE_LEN]);
+    let ciphertext = &data[1 + SALT_LEN + NONCE_LEN..];
+    let cipher = XChaCha20Poly1305::new(key.into());
+    let plaintext = cipher
+        .decrypt(nonce, ciphertext)
+        .map_err(|_| "wrong passphrase".to_string())?;
+    if let Ok(export) = serde_json::from_slice::<VaultExport>(&plaintext) {
+        return Ok(export);
+    }
+    // A vault encrypted under the pre-SQLite scheme sealed a bare
+    // `Vec<Note>` rather than a `VaultExport`; fall back to that shape so
+    // such a vault still unlocks instead of failing with a parse error.
+    let notes: Vec<Note> = serde_json::from_slice(&plaintext).map_err(|e| e.to_string())?;
+    Ok(VaultExport {
+        notes,
+        revisions: Vec::new(),
+    })
 }
 
 fn now_unix() -> u64 {
@@ -49,15 +593,141 @@ fn now_unix() -> u64 {
         .as_secs()
 }
 
+/// Walks a Tiptap JSON document collecting the string value of every
+/// `"text"` node, ignoring marks/attrs and everything else.
+fn extract_text(content: &str) -> String {
+    let value: serde_json::Value = match serde_json::from_str(content) {
+        Ok(v) => v,
+        Err(_) => return String::new(),
+    };
+    let mut out = String::new();
+    collect_text(&value, &mut out);
+    out
+}
+
+fn collect_text(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::Object(map) => {
+            if let Some(serde_json::Value::String(s)) = map.get("text") {
+                if !out.is_empty() {
+                    out.push(' ');
+                }
+                out.push_str(s);
+            }
+            for (key, v) in map {
+                if key != "text" {
+                    collect_text(v, out);
+                }
+            }
+        }
+        serde_json::Value::Array(arr) => {
+            for v in arr {
+                collect_text(v, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn build_index(notes: &[Note]) -> InvertedIndex {
+    let mut index = InvertedIndex::new();
+    for note in notes {
+        index_note(&mut index, note);
+    }
+    index
+}
+
+fn index_note(index: &mut InvertedIndex, note: &Note) {
+    for token in tokenize(&extract_text(&note.content)) {
+        *index.entry(token).or_default().entry(note.id.clone()).or_insert(0) += 1;
+    }
+}
+
+fn deindex_note(index: &mut InvertedIndex, note_id: &str) {
+    for counts in index.values_mut() {
+        counts.remove(note_id);
+    }
+}
+
+fn reindex_note(index: &mut InvertedIndex, note: &Note) {
+    deindex_note(index, &note.id);
+    index_note(index, note);
+}
+
+/// Standard DP edit-distance matrix.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let (la, lb) = (a.len(), b.len());
+    let mut dp = vec![vec![0usize; lb + 1]; la + 1];
+    for (i, row) in dp.iter_mut().enumerate().take(la + 1) {
+        row[0] = i;
+    }
+    for j in 0..=lb {
+        dp[0][j] = j;
+    }
+    for i in 1..=la {
+        for j in 1..=lb {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            dp[i][j] = (dp[i - 1][j] + 1)
+                .min(dp[i][j - 1] + 1)
+                .min(dp[i - 1][j - 1] + cost);
+        }
+    }
+    dp[la][lb]
+}
+
+/// Short tokens must match exactly; mid-length tokens tolerate one typo,
+/// longer tokens tolerate two.
+fn max_typo_distance(len: usize) -> usize {
+    match len {
+        0..=4 => 0,
+        5..=8 => 1,
+        _ => 2,
+    }
+}
+
+fn make_snippet(text: &str, query_tokens: &[String]) -> String {
+    const RADIUS: usize = 40;
+    let lower = text.to_lowercase();
+    let hit_pos = query_tokens
+        .iter()
+        .filter_map(|t| lower.find(t.as_str()))
+        .min();
+    match hit_pos {
+        Some(pos) => {
+            let start = pos.saturating_sub(RADIUS);
+            let end = (pos + RADIUS).min(text.len());
+            let start = text.char_indices().map(|(i, _)| i).find(|&i| i >= start).unwrap_or(0);
+            let end = text
+                .char_indices()
+                .map(|(i, _)| i)
+                .find(|&i| i >= end)
+                .unwrap_or(text.len());
+            text[start..end].trim().to_string()
+        }
+        None => text.chars().take(RADIUS * 2).collect(),
+    }
+}
+
 #[tauri::command]
 fn get_notes(state: tauri::State<AppState>) -> Result<Vec<Note>, String> {
-    let notes = state.notes.lock().map_err(|e| e.to_string())?;
-    Ok(notes.clone())
+    with_active(&state, |vault| {
+        let conn = vault.conn.lock().map_err(|e| e.to_string())?;
+        fetch_all_notes(&conn)
+    })
 }
 
 #[tauri::command]
 fn create_note(state: tauri::State<AppState>, list: String) -> Result<Note, String> {
-    let mut notes = state.notes.lock().map_err(|e| e.to_string())?;
     let now = now_unix();
     let note = Note {
         id: Uuid::new_v4().to_string(),
@@ -68,10 +738,30 @@ fn create_note(state: tauri::State<AppState>, list: String) -> Result<Note, Stri
         list,
         deleted: false,
         deleted_at: None,
+        tags: Vec::new(),
     };
-    notes.push(note.clone());
-    save_notes(&state.file_path, &notes)?;
-    Ok(note)
+    with_active_mut(&state, |vault| {
+        let conn = vault.conn.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO notes
+                (id, content, created_at, updated_at, pinned, list, deleted, deleted_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                note.id,
+                note.content,
+                note.created_at,
+                note.updated_at,
+                note.pinned as i64,
+                note.list,
+                note.deleted as i64,
+                note.deleted_at,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        drop(conn);
+        index_note(&mut vault.index.lock().map_err(|e| e.to_string())?, &note);
+        Ok(note.clone())
+    })
 }
 
 #[tauri::command]
@@ -80,16 +770,23 @@ fn update_note(
     id: String,
     content: String,
 ) -> Result<Note, String> {
-    let mut notes = state.notes.lock().map_err(|e| e.to_string())?;
-    let note = notes
-        .iter_mut()
-        .find(|n| n.id == id)
-        .ok_or_else(|| format!("Note {} not found", id))?;
-    note.content = content;
-    note.updated_at = now_unix();
-    let note = note.clone();
-    save_notes(&state.file_path, &notes)?;
-    Ok(note)
+    let now = now_unix();
+    with_active_mut(&state, |vault| {
+        let conn = vault.conn.lock().map_err(|e| e.to_string())?;
+        let existing = fetch_note(&conn, &id)?;
+        if existing.content != content {
+            snapshot_revision(&conn, &id, &existing.content)?;
+        }
+        conn.execute(
+            "UPDATE notes SET content = ?1, updated_at = ?2 WHERE id = ?3",
+            params![content, now, id],
+        )
+        .map_err(|e| e.to_string())?;
+        let note = fetch_note(&conn, &id)?;
+        drop(conn);
+        reindex_note(&mut vault.index.lock().map_err(|e| e.to_string())?, &note);
+        Ok(note)
+    })
 }
 
 #[tauri::command]
@@ -98,62 +795,471 @@ fn update_note_list(
     old_list: String,
     new_list: String,
 ) -> Result<(), String> {
-    let mut notes = state.notes.lock().map_err(|e| e.to_string())?;
-    for note in notes.iter_mut() {
-        if note.list == old_list {
-            note.list = new_list.clone();
-        }
-    }
-    save_notes(&state.file_path, &notes)
+    with_active_mut(&state, |vault| {
+        let conn = vault.conn.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE notes SET list = ?1 WHERE list = ?2",
+            params![new_list, old_list],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    })
 }
 
 #[tauri::command]
 fn pin_note(state: tauri::State<AppState>, id: String, pinned: bool) -> Result<Note, String> {
-    let mut notes = state.notes.lock().map_err(|e| e.to_string())?;
-    let note = notes
-        .iter_mut()
-        .find(|n| n.id == id)
-        .ok_or_else(|| format!("Note {} not found", id))?;
-    note.pinned = pinned;
-    let note = note.clone();
-    save_notes(&state.file_path, &notes)?;
-    Ok(note)
+    with_active_mut(&state, |vault| {
+        let conn = vault.conn.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE notes SET pinned = ?1 WHERE id = ?2",
+            params![pinned as i64, id],
+        )
+        .map_err(|e| e.to_string())?;
+        fetch_note(&conn, &id)
+    })
 }
 
 #[tauri::command]
 fn soft_delete_note(state: tauri::State<AppState>, id: String) -> Result<Note, String> {
-    let mut notes = state.notes.lock().map_err(|e| e.to_string())?;
-    let note = notes
-        .iter_mut()
-        .find(|n| n.id == id)
-        .ok_or_else(|| format!("Note {} not found", id))?;
-    note.deleted = true;
-    note.deleted_at = Some(now_unix());
-    note.pinned = false;
-    let note = note.clone();
-    save_notes(&state.file_path, &notes)?;
-    Ok(note)
+    let now = now_unix();
+    with_active_mut(&state, |vault| {
+        let conn = vault.conn.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE notes SET deleted = 1, deleted_at = ?1, pinned = 0 WHERE id = ?2",
+            params![now, id],
+        )
+        .map_err(|e| e.to_string())?;
+        fetch_note(&conn, &id)
+    })
 }
 
 #[tauri::command]
 fn restore_note(state: tauri::State<AppState>, id: String) -> Result<Note, String> {
-    let mut notes = state.notes.lock().map_err(|e| e.to_string())?;
-    let note = notes
-        .iter_mut()
-        .find(|n| n.id == id)
-        .ok_or_else(|| format!("Note {} not found", id))?;
-    note.deleted = false;
-    note.deleted_at = None;
-    let note = note.clone();
-    save_notes(&state.file_path, &notes)?;
-    Ok(note)
+    with_active_mut(&state, |vault| {
+        let conn = vault.conn.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE notes SET deleted = 0, deleted_at = NULL WHERE id = ?1",
+            params![id],
+        )
+        .map_err(|e| e.to_string())?;
+        fetch_note(&conn, &id)
+    })
 }
 
 #[tauri::command]
 fn delete_note(state: tauri::State<AppState>, id: String) -> Result<(), String> {
-    let mut notes = state.notes.lock().map_err(|e| e.to_string())?;
-    notes.retain(|n| n.id != id);
-    save_notes(&state.file_path, &notes)
+    with_active_mut(&state, |vault| {
+        let conn = vault.conn.lock().map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM notes WHERE id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM note_tags WHERE note_id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM revisions WHERE note_id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        drop(conn);
+        deindex_note(&mut vault.index.lock().map_err(|e| e.to_string())?, &id);
+        Ok(())
+    })
+}
+
+/// Attaches `tag` to a note, normalized to a lowercase trimmed string and
+/// deduped on insert.
+#[tauri::command]
+fn add_tag(state: tauri::State<AppState>, id: String, tag: String) -> Result<Note, String> {
+    let tag = normalize_tag(&tag);
+    with_active_mut(&state, |vault| {
+        let conn = vault.conn.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR IGNORE INTO note_tags (note_id, tag) VALUES (?1, ?2)",
+            params![id, tag],
+        )
+        .map_err(|e| e.to_string())?;
+        fetch_note(&conn, &id)
+    })
+}
+
+#[tauri::command]
+fn remove_tag(state: tauri::State<AppState>, id: String, tag: String) -> Result<Note, String> {
+    let tag = normalize_tag(&tag);
+    with_active_mut(&state, |vault| {
+        let conn = vault.conn.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "DELETE FROM note_tags WHERE note_id = ?1 AND tag = ?2",
+            params![id, tag],
+        )
+        .map_err(|e| e.to_string())?;
+        fetch_note(&conn, &id)
+    })
+}
+
+/// Returns every tag in use, with how many non-deleted notes carry it.
+#[tauri::command]
+fn get_all_tags(state: tauri::State<AppState>) -> Result<Vec<(String, usize)>, String> {
+    with_active(&state, |vault| {
+        let conn = vault.conn.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT nt.tag, COUNT(*) FROM note_tags nt
+                 JOIN notes n ON n.id = nt.note_id
+                 WHERE n.deleted = 0
+                 GROUP BY nt.tag
+                 ORDER BY nt.tag",
+            )
+            .map_err(|e| e.to_string())?;
+        stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())
+    })
+}
+
+/// Returns notes carrying any (or, with `match_all`, all) of `tags`.
+#[tauri::command]
+fn notes_by_tags(
+    state: tauri::State<AppState>,
+    tags: Vec<String>,
+    match_all: bool,
+) -> Result<Vec<Note>, String> {
+    let tags: Vec<String> = tags.iter().map(|t| normalize_tag(t)).collect();
+    if tags.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    with_active(&state, |vault| {
+        let conn = vault.conn.lock().map_err(|e| e.to_string())?;
+        let placeholders = (1..=tags.len())
+            .map(|i| format!("?{}", i))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = if match_all {
+            format!(
+                "SELECT n.* FROM notes n
+                 JOIN note_tags nt ON nt.note_id = n.id
+                 WHERE nt.tag IN ({})
+                 GROUP BY n.id
+                 HAVING COUNT(DISTINCT nt.tag) = ?{}",
+                placeholders,
+                tags.len() + 1
+            )
+        } else {
+            format!(
+                "SELECT DISTINCT n.* FROM notes n
+                 JOIN note_tags nt ON nt.note_id = n.id
+                 WHERE nt.tag IN ({})",
+                placeholders
+            )
+        };
+
+        let tags_len = tags.len() as i64;
+        let mut params_vec: Vec<&dyn rusqlite::ToSql> =
+            tags.iter().map(|t| t as &dyn rusqlite::ToSql).collect();
+        if match_all {
+            params_vec.push(&tags_len);
+        }
+
+        let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+        let mut notes = stmt
+            .query_map(params_vec.as_slice(), row_to_note)
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        for note in notes.iter_mut() {
+            note.tags = fetch_tags(&conn, &note.id)?;
+        }
+        Ok(notes)
+    })
+}
+
+/// Returns the trash retention window (in days) so the frontend can work
+/// out when a trashed note is due for deletion.
+#[tauri::command]
+fn get_trash_retention(state: tauri::State<AppState>) -> u32 {
+    load_settings(&state.data_dir).trash_retention_days
+}
+
+/// Persists the trash retention window (in days) used by the startup and
+/// unlock-time sweeps.
+#[tauri::command]
+fn set_trash_retention(state: tauri::State<AppState>, days: u32) -> Result<(), String> {
+    save_settings(
+        &state.data_dir,
+        &Settings {
+            trash_retention_days: days,
+        },
+    )
+}
+
+/// Hard-deletes every trashed note in the active vault right now,
+/// regardless of the retention window. Returns how many were removed.
+#[tauri::command]
+fn empty_trash(state: tauri::State<AppState>) -> Result<usize, String> {
+    with_active_mut(&state, |vault| {
+        let conn = vault.conn.lock().map_err(|e| e.to_string())?;
+        let ids: Vec<String> = {
+            let mut stmt = conn
+                .prepare("SELECT id FROM notes WHERE deleted = 1")
+                .map_err(|e| e.to_string())?;
+            stmt.query_map([], |row| row.get(0))
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?
+        };
+        purge_notes(&conn, &ids)?;
+        let removed = ids.len();
+        drop(conn);
+        let mut index = vault.index.lock().map_err(|e| e.to_string())?;
+        for id in &ids {
+            deindex_note(&mut index, id);
+        }
+        Ok(removed)
+    })
+}
+
+/// Lists notes currently in the trash, so the frontend can show what's
+/// scheduled for deletion and by when.
+#[tauri::command]
+fn get_trashed_notes(state: tauri::State<AppState>) -> Result<Vec<Note>, String> {
+    with_active(&state, |vault| {
+        let conn = vault.conn.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM notes WHERE deleted = 1",
+                NOTE_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let mut notes = stmt
+            .query_map([], row_to_note)
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        for note in notes.iter_mut() {
+            note.tags = fetch_tags(&conn, &note.id)?;
+        }
+        Ok(notes)
+    })
+}
+
+/// Returns a note's past content snapshots, newest first.
+#[tauri::command]
+fn get_note_history(state: tauri::State<AppState>, id: String) -> Result<Vec<Revision>, String> {
+    with_active(&state, |vault| {
+        let conn = vault.conn.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, content, created_at FROM revisions WHERE note_id = ?1 ORDER BY created_at DESC",
+            )
+            .map_err(|e| e.to_string())?;
+        let revisions = stmt
+            .query_map(params![id], |row| {
+                Ok(Revision {
+                    id: row.get(0)?,
+                    content: row.get(1)?,
+                    created_at: row.get(2)?,
+                })
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        Ok(revisions)
+    })
+}
+
+/// Restores a note to a prior revision's content, snapshotting the current
+/// content first so the restore itself can be undone.
+#[tauri::command]
+fn restore_revision(
+    state: tauri::State<AppState>,
+    id: String,
+    revision_id: String,
+) -> Result<Note, String> {
+    with_active_mut(&state, |vault| {
+        let conn = vault.conn.lock().map_err(|e| e.to_string())?;
+        let existing = fetch_note(&conn, &id)?;
+        let target_content: String = conn
+            .query_row(
+                "SELECT content FROM revisions WHERE id = ?1 AND note_id = ?2",
+                params![revision_id, id],
+                |row| row.get(0),
+            )
+            .map_err(|_| format!("Revision {} not found", revision_id))?;
+
+        conn.execute(
+            "INSERT INTO revisions (id, note_id, content, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![Uuid::new_v4().to_string(), id, existing.content, now_unix()],
+        )
+        .map_err(|e| e.to_string())?;
+        cap_revisions(&conn, &id)?;
+
+        conn.execute(
+            "UPDATE notes SET content = ?1, updated_at = ?2 WHERE id = ?3",
+            params![target_content, now_unix(), id],
+        )
+        .map_err(|e| e.to_string())?;
+        let note = fetch_note(&conn, &id)?;
+        drop(conn);
+        reindex_note(&mut vault.index.lock().map_err(|e| e.to_string())?, &note);
+        Ok(note)
+    })
+}
+
+/// Encrypts the active vault under a passphrase for the first time,
+/// deriving an Argon2id key with a fresh random salt. From this point on
+/// the live connection runs in memory, and `db_path` holds only the
+/// XChaCha20-Poly1305-sealed export, re-written after every change by
+/// `with_active_mut` so it never drifts from what's in memory.
+#[tauri::command]
+fn set_passphrase(state: tauri::State<AppState>, passphrase: String) -> Result<(), String> {
+    let mut salt = [0u8; SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let key = derive_key(&passphrase, &salt)?;
+    let vault_key = VaultKey { key, salt };
+
+    with_active(&state, |vault| {
+        let mut conn = vault.conn.lock().map_err(|e| e.to_string())?;
+        let export = export_vault(&conn)?;
+
+        let in_memory = Connection::open_in_memory().map_err(|e| e.to_string())?;
+        init_schema(&in_memory)?;
+        import_vault(&in_memory, &export)?;
+        *conn = in_memory;
+        drop(conn);
+
+        fs::write(&vault.db_path, encrypt_vault(&export, &vault_key)?).map_err(|e| e.to_string())?;
+        *vault.vault_key.lock().map_err(|e| e.to_string())? = Some(vault_key);
+        Ok(())
+    })
+}
+
+/// Unlocks the active vault, decrypting its on-disk export with
+/// `passphrase` and loading it into a fresh in-memory connection. A failed
+/// MAC check (wrong passphrase) is reported as a distinct error so the
+/// frontend can show "wrong passphrase" instead of a generic one.
+#[tauri::command]
+fn unlock(state: tauri::State<AppState>, passphrase: String) -> Result<(), String> {
+    let retention_days = load_settings(&state.data_dir).trash_retention_days;
+    with_active(&state, |vault| {
+        let data = fs::read(&vault.db_path).map_err(|e| e.to_string())?;
+        let salt = header_salt(&data)?;
+        let key = derive_key(&passphrase, &salt)?;
+        let export = decrypt_vault(&data, &key)?;
+
+        let conn = Connection::open_in_memory().map_err(|e| e.to_string())?;
+        init_schema(&conn)?;
+        import_vault(&conn, &export)?;
+        let removed = sweep_trash(&conn, retention_days)?;
+
+        let notes = fetch_all_notes(&conn)?;
+        *vault.index.lock().map_err(|e| e.to_string())? = build_index(&notes);
+        *vault.vault_key.lock().map_err(|e| e.to_string())? = Some(VaultKey { key, salt });
+        *vault.conn.lock().map_err(|e| e.to_string())? = conn;
+        if removed > 0 {
+            persist_if_encrypted(vault)?;
+        }
+        Ok(())
+    })
+}
+
+/// Searches the active vault's note text for `query`, tolerating small
+/// typos and boosting prefix matches, and returns hits ranked by
+/// descending score.
+#[tauri::command]
+fn search_notes(
+    state: tauri::State<AppState>,
+    query: String,
+    include_trashed: Option<bool>,
+) -> Result<Vec<SearchHit>, String> {
+    let include_trashed = include_trashed.unwrap_or(false);
+    let query_tokens = tokenize(&query);
+    if query_tokens.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    with_active(&state, |vault| {
+        let conn = vault.conn.lock().map_err(|e| e.to_string())?;
+        let index = vault.index.lock().map_err(|e| e.to_string())?;
+
+        let mut scores: HashMap<String, f64> = HashMap::new();
+        for q in &query_tokens {
+            let max_distance = max_typo_distance(q.len());
+            for (token, counts) in index.iter() {
+                let is_prefix = token.starts_with(q.as_str());
+                let distance = if token == q { 0 } else { levenshtein(q, token) };
+                // A prefix match (e.g. query "note" against indexed token
+                // "notes") racks up one distance point per extra character,
+                // which the typo-distance gate below would otherwise drop
+                // even though it's not a typo at all -- bypass the gate for
+                // prefix matches and only use `distance` to grade the score.
+                if !is_prefix && distance > max_distance {
+                    continue;
+                }
+                let prefix_boost = if is_prefix { 2.0 } else { 1.0 };
+                let weight = prefix_boost / (1.0 + distance as f64);
+                for (note_id, count) in counts {
+                    *scores.entry(note_id.clone()).or_insert(0.0) += *count as f64 * weight;
+                }
+            }
+        }
+
+        let mut hits: Vec<SearchHit> = scores
+            .into_iter()
+            .filter_map(|(note_id, score)| {
+                let note = fetch_note(&conn, &note_id).ok()?;
+                if note.deleted && !include_trashed {
+                    return None;
+                }
+                let snippet = make_snippet(&extract_text(&note.content), &query_tokens);
+                Some(SearchHit {
+                    note,
+                    score,
+                    snippet,
+                })
+            })
+            .collect();
+
+        hits.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        Ok(hits)
+    })
+}
+
+#[tauri::command]
+fn list_vaults(state: tauri::State<AppState>) -> Result<Vec<VaultInfo>, String> {
+    Ok(load_vaults_manifest(&state.data_dir))
+}
+
+#[tauri::command]
+fn create_vault(state: tauri::State<AppState>, name: String) -> Result<VaultInfo, String> {
+    let info = VaultInfo {
+        id: Uuid::new_v4().to_string(),
+        name,
+    };
+
+    let mut manifest = load_vaults_manifest(&state.data_dir);
+    manifest.push(info.clone());
+    save_vaults_manifest(&state.data_dir, &manifest)?;
+
+    let retention_days = load_settings(&state.data_dir).trash_retention_days;
+    let vault_state = open_vault_state(&state.data_dir, &info.id, retention_days)?;
+    state
+        .vaults
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(info.id.clone(), vault_state);
+    Ok(info)
+}
+
+#[tauri::command]
+fn switch_vault(state: tauri::State<AppState>, id: String) -> Result<(), String> {
+    let known = state
+        .vaults
+        .lock()
+        .map_err(|e| e.to_string())?
+        .contains_key(&id);
+    if !known {
+        return Err(format!("Vault {} not found", id));
+    }
+    save_active_vault_id(&state.data_dir, &id)?;
+    *state.active.lock().map_err(|e| e.to_string())? = id;
+    Ok(())
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -165,11 +1271,35 @@ pub fn run() {
         .setup(|app| {
             let data_dir = app.path().app_data_dir()?;
             fs::create_dir_all(&data_dir)?;
-            let file_path = data_dir.join("notes.json");
-            let notes = load_notes(&file_path);
+            fs::create_dir_all(data_dir.join("vaults"))?;
+
+            let mut manifest = load_vaults_manifest(&data_dir);
+            if manifest.is_empty() {
+                manifest.push(VaultInfo {
+                    id: DEFAULT_VAULT_ID.to_string(),
+                    name: "My Notes".to_string(),
+                });
+                save_vaults_manifest(&data_dir, &manifest)?;
+            }
+
+            let active_id = load_active_vault_id(&data_dir)
+                .filter(|id| manifest.iter().any(|v| &v.id == id))
+                .unwrap_or_else(|| manifest[0].id.clone());
+            save_active_vault_id(&data_dir, &active_id)?;
+
+            let retention_days = load_settings(&data_dir).trash_retention_days;
+            let mut vaults = HashMap::new();
+            for info in &manifest {
+                vaults.insert(
+                    info.id.clone(),
+                    open_vault_state(&data_dir, &info.id, retention_days)?,
+                );
+            }
+
             app.manage(AppState {
-                notes: Mutex::new(notes),
-                file_path,
+                data_dir,
+                vaults: Mutex::new(vaults),
+                active: Mutex::new(active_id),
             });
             Ok(())
         })
@@ -182,6 +1312,22 @@ pub fn run() {
             soft_delete_note,
             restore_note,
             delete_note,
+            search_notes,
+            add_tag,
+            remove_tag,
+            get_all_tags,
+            notes_by_tags,
+            get_trash_retention,
+            set_trash_retention,
+            empty_trash,
+            get_trashed_notes,
+            get_note_history,
+            restore_revision,
+            set_passphrase,
+            unlock,
+            list_vaults,
+            create_vault,
+            switch_vault,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");